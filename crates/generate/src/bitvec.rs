@@ -1,16 +1,39 @@
 use std::{
     hash::{Hash, Hasher},
+    io::{self, Read, Write},
     ptr,
 };
 
 const ARENA_CHUNK_WORDS: usize = 128 * 1024 / std::mem::size_of::<u64>();
 
+/// Number of power-of-two size classes the free list buckets allocations
+/// into, indexed by `trailing_zeros()` of the rounded size. `1 << 26` words
+/// is 512 MiB, comfortably past any single token set this crate builds.
+const NUM_SIZE_CLASSES: usize = 27;
+
+/// Largest word count a single `BitVec` region can request. `arena_alloc`
+/// rounds `n_words + 1` (the header) up to a power of two and buckets it by
+/// `trailing_zeros()`, so anything whose header-inclusive size would round
+/// up to `1 << NUM_SIZE_CLASSES` or beyond has no bucket to land in;
+/// `WordArena::alloc` asserts against this so the out-of-bounds size class
+/// panics clearly instead of indexing `free_list` out of bounds. Callers
+/// that accept a size from outside the process (e.g. `BitVec::read_from`)
+/// should still check untrusted sizes against this bound up front, so a
+/// corrupt or adversarial input produces a normal error instead of a panic.
+const MAX_ARENA_ALLOC_WORDS: usize = (1 << (NUM_SIZE_CLASSES - 1)) - 1;
+
 struct WordArena {
     chunks: Vec<Vec<u64>>,
     offset: usize,
-    /// Freed blocks grouped by size (number of words). Checked before
+    /// Freed blocks bucketed by power-of-two size class (see
+    /// `NUM_SIZE_CLASSES`), so a block freed at one size can be handed back
+    /// out to any request that rounds up to the same class. Checked before
     /// bump-allocating so that dropped `BitVec`s can be reused immediately.
-    free_list: Vec<(usize, Vec<*mut u64>)>,
+    free_list: [Vec<*mut u64>; NUM_SIZE_CLASSES],
+    /// Bumped every time `reset()` runs. Stamped onto each `BitVec` at
+    /// allocation time so a debug build can catch one outliving the
+    /// `ArenaScope` that reset the arena out from under it.
+    generation: u32,
 }
 
 impl WordArena {
@@ -18,40 +41,78 @@ impl WordArena {
         Self {
             chunks: Vec::new(),
             offset: ARENA_CHUNK_WORDS, // forces first alloc to create a chunk
-            free_list: Vec::new(),
+            free_list: [const { Vec::new() }; NUM_SIZE_CLASSES],
+            generation: 0,
         }
     }
 
-    #[inline]
-    fn alloc(&mut self, n_words: usize) -> *mut u64 {
-        if n_words == 0 {
-            return std::ptr::NonNull::<u64>::dangling().as_ptr();
+    /// Drop everything allocated so far in one shot, following the "destroy
+    /// everything at once" model of arena allocators like `rustc_arena`'s
+    /// `TypedArena`: retain a single chunk (so the next parse doesn't pay for
+    /// reallocating it) and bump-allocate back into it from the start, free
+    /// the rest, and discard the free list along with it since its pointers
+    /// are now dangling.
+    fn reset(&mut self) {
+        self.chunks.truncate(1);
+        match self.chunks.first_mut() {
+            // Re-zero the retained chunk: it was bump-allocated into a real
+            // generation, so it may hold nonzero words that the invariant
+            // "data[words_in_use..capacity] is always zero" depends on once
+            // we start handing them out again from offset 0.
+            Some(chunk) => {
+                chunk.fill(0);
+                self.offset = 0;
+            }
+            // No chunk has ever been allocated on this thread; force the
+            // next `alloc` to push one instead of bump-allocating into a
+            // chunk that doesn't exist.
+            None => self.offset = ARENA_CHUNK_WORDS,
         }
-        // Check the free list before bump-allocating.
-        if let Some((_, bucket)) = self.free_list.iter_mut().find(|(s, _)| *s == n_words)
-            && let Some(ptr) = bucket.pop()
-        {
-            return ptr;
+        for bucket in &mut self.free_list {
+            bucket.clear();
         }
-        if self.offset + n_words > ARENA_CHUNK_WORDS {
-            let size = ARENA_CHUNK_WORDS.max(n_words);
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Round `n_words` up to a power of two, bump-allocate (or reuse a freed
+    /// block of the same size class), and return the block along with the
+    /// rounded size actually reserved.
+    #[inline]
+    fn alloc(&mut self, n_words: usize) -> (*mut u64, usize) {
+        if n_words == 0 {
+            return (std::ptr::NonNull::<u64>::dangling().as_ptr(), 0);
+        }
+        let rounded = n_words.next_power_of_two();
+        let class = rounded.trailing_zeros() as usize;
+        assert!(
+            class < NUM_SIZE_CLASSES,
+            "arena allocation of {n_words} words exceeds the maximum arena allocation size"
+        );
+        if let Some(ptr) = self.free_list[class].pop() {
+            return (ptr, rounded);
+        }
+        if self.offset + rounded > ARENA_CHUNK_WORDS {
+            let size = ARENA_CHUNK_WORDS.max(rounded);
             self.chunks.push(vec![0u64; size]);
             self.offset = 0;
         }
         let chunk = self.chunks.last_mut().unwrap();
-        // SAFETY: Either a new chunk was just created with len = ARENA_CHUNK_WORDS.max(n_words),
-        // in which case offset = 0 and n_words <= len; or an existing chunk is reused, in which
-        // case offset + n_words <= ARENA_CHUNK_WORDS <= chunk.len().
+        // SAFETY: Either a new chunk was just created with len = ARENA_CHUNK_WORDS.max(rounded),
+        // in which case offset = 0 and rounded <= len; or an existing chunk is reused, in which
+        // case offset + rounded <= ARENA_CHUNK_WORDS <= chunk.len().
         let ptr = unsafe { chunk.as_mut_ptr().add(self.offset) };
-        self.offset += n_words;
-        ptr
+        self.offset += rounded;
+        (ptr, rounded)
     }
 
+    /// Return a block of `n_words` words (which must be a power of two, as
+    /// handed out by `alloc`) to its size class's free list.
     #[inline]
     fn free(&mut self, ptr: *mut u64, n_words: usize, used_words: usize) {
         if n_words == 0 {
             return;
         }
+        debug_assert!(n_words.is_power_of_two());
         // Zero only the in-use words; the rest are already zero by the BitVec
         // invariant (data[words_in_use..capacity] is always zero).
         // SAFETY: ptr was returned by alloc(n_words) and is valid for n_words
@@ -59,11 +120,8 @@ impl WordArena {
         if used_words > 0 {
             unsafe { std::slice::from_raw_parts_mut(ptr, used_words).fill(0) };
         }
-        if let Some((_, bucket)) = self.free_list.iter_mut().find(|(s, _)| *s == n_words) {
-            bucket.push(ptr);
-        } else {
-            self.free_list.push((n_words, vec![ptr]));
-        }
+        let class = n_words.trailing_zeros() as usize;
+        self.free_list[class].push(ptr);
     }
 }
 
@@ -72,24 +130,104 @@ thread_local! {
 }
 
 #[inline]
-fn arena_alloc(n_words: usize) -> *mut u64 {
-    WORD_ARENA.with(|a| a.borrow_mut().alloc(n_words))
+fn arena_generation() -> u32 {
+    WORD_ARENA.with(|a| a.borrow().generation)
+}
+
+/// RAII guard that resets the thread-local arena when dropped, reclaiming
+/// every word the arena has handed out on this thread, including words
+/// allocated before the guard was created. Wrap one file's worth
+/// of table construction in a scope so the backing memory is returned
+/// instead of growing for the lifetime of the process:
+///
+/// ```ignore
+/// let _scope = ArenaScope::new();
+/// // ... build tables, cloning and OR-ing BitVecs freely ...
+/// // all of that memory is reclaimed when `_scope` drops here.
+/// ```
+///
+/// No `BitVec` alive when the scope drops, whether allocated inside the
+/// scope or before it, may be used or dropped afterwards; in debug builds
+/// this is checked by comparing the arena generation stamped on the
+/// `BitVec` against the arena's current generation.
+#[must_use]
+pub struct ArenaScope(());
+
+impl ArenaScope {
+    pub fn new() -> Self {
+        Self(())
+    }
 }
 
+impl Default for ArenaScope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ArenaScope {
+    fn drop(&mut self) {
+        WORD_ARENA.with(|a| a.borrow_mut().reset());
+    }
+}
+
+/// Allocate at least `n_words` words plus one leading header word, and
+/// return a pointer just past the header along with the actual number of
+/// data words reserved (the header-inclusive size is rounded up to a power
+/// of two by the arena's size-class free list, so this is one less than a
+/// power of two and may exceed `n_words`). The header starts
+/// life holding a refcount of 1, turning the region into a `Bytes`-style
+/// shared buffer: `clone()` can hand out another handle to the same region
+/// by bumping the header instead of copying, and the region is only
+/// returned to the arena once the count drops to zero.
+#[inline]
+fn arena_alloc(n_words: usize) -> (*mut u64, usize) {
+    if n_words == 0 {
+        return (ptr::NonNull::<u64>::dangling().as_ptr(), 0);
+    }
+    let (raw, rounded) = WORD_ARENA.with(|a| a.borrow_mut().alloc(n_words + 1));
+    // SAFETY: raw points to `rounded` valid, zeroed words just allocated.
+    unsafe { raw.write(1) };
+    (unsafe { raw.add(1) }, rounded - 1)
+}
+
+/// Release one reference to the region backing `data` (a pointer previously
+/// returned by `arena_alloc`). Decrements the header refcount and only
+/// returns the region to the arena once no handle is left pointing at it.
 #[inline]
-fn arena_free(ptr: *mut u64, n_words: usize, used_words: usize) {
-    WORD_ARENA.with(|a| a.borrow_mut().free(ptr, n_words, used_words));
+fn arena_free(data: *mut u64, n_words: usize, used_words: usize) {
+    if n_words == 0 {
+        return;
+    }
+    // SAFETY: data was returned by arena_alloc(n_words), so data[-1] is the
+    // refcount header written by that call.
+    let header = unsafe { data.sub(1) };
+    let count = unsafe { *header };
+    if count > 1 {
+        unsafe { *header = count - 1 };
+        return;
+    }
+    WORD_ARENA.with(|a| a.borrow_mut().free(header, n_words + 1, used_words + 1));
 }
 
 /// A bit vector whose backing `u64` words are bump-allocated from a global
 /// arena. Token sets are OR'd together many times and doing this at the word
 /// level rather than bit-by-bit is much faster.
+///
+/// Backing regions are reference-counted so that `clone()` is a cheap handle
+/// copy rather than a fresh allocation: the first write to a shared region
+/// (`set`, `resize`, `pop`, `insert_all`, ...) calls `make_unique()` to copy
+/// out a private region if, and only if, other handles still point at it.
 pub struct BitVec {
     /// Pointer into arena chunk data. Dangling when `capacity == 0`.
     data: *mut u64,
     num_bits: u32,
     /// Number of allocated words (_not_ bytes) in the arena region.
     capacity: u32,
+    /// Arena generation this region was allocated in, used to debug-assert
+    /// that this `BitVec` doesn't outlive the `ArenaScope` that reset it.
+    /// Meaningless when `capacity == 0`.
+    generation: u32,
 }
 
 impl BitVec {
@@ -98,6 +236,7 @@ impl BitVec {
             data: ptr::NonNull::dangling().as_ptr(),
             num_bits: 0,
             capacity: 0,
+            generation: 0,
         }
     }
 
@@ -106,10 +245,12 @@ impl BitVec {
         if n_words == 0 {
             return Self::new();
         }
+        let (data, capacity) = arena_alloc(n_words);
         Self {
-            data: arena_alloc(n_words),
+            data,
             num_bits: 0,
-            capacity: n_words as u32,
+            capacity: capacity as u32,
+            generation: arena_generation(),
         }
     }
 
@@ -118,9 +259,24 @@ impl BitVec {
         (self.num_bits as usize).div_ceil(64)
     }
 
+    /// Debug-assert that the arena hasn't been reset since this region was
+    /// allocated, i.e. that this `BitVec` hasn't outlived its `ArenaScope`.
+    #[inline]
+    fn debug_check_generation(&self) {
+        if self.capacity == 0 {
+            return;
+        }
+        debug_assert_eq!(
+            self.generation,
+            arena_generation(),
+            "BitVec outlived the ArenaScope it was allocated in"
+        );
+    }
+
     /// View the in-use words as a slice.
     #[inline]
-    pub const fn as_slice(&self) -> &[u64] {
+    pub fn as_slice(&self) -> &[u64] {
+        self.debug_check_generation();
         let n = self.words_in_use();
         if n == 0 {
             &[]
@@ -133,7 +289,8 @@ impl BitVec {
 
     /// View all `capacity` allocated words as a mutable slice.
     #[inline]
-    const fn as_full_slice_mut(&mut self) -> &mut [u64] {
+    fn as_full_slice_mut(&mut self) -> &mut [u64] {
+        self.debug_check_generation();
         let n = self.capacity as usize;
         if n == 0 {
             return &mut [];
@@ -153,7 +310,36 @@ impl BitVec {
         Some(self.as_slice()[index / 64] >> (index % 64) & 1 != 0)
     }
 
+    /// If this region is shared with other `BitVec` handles (refcount > 1),
+    /// copy it into a private region first. Every method that writes through
+    /// `data` calls this before touching a single word.
+    fn make_unique(&mut self) {
+        self.debug_check_generation();
+        if self.capacity == 0 {
+            return;
+        }
+        // SAFETY: data was returned by arena_alloc, so data[-1] is the header.
+        let header = unsafe { self.data.sub(1) };
+        if unsafe { *header } <= 1 {
+            return;
+        }
+        let words = self.words_in_use();
+        let (new_data, new_capacity) = arena_alloc(self.capacity as usize);
+        if words > 0 {
+            // SAFETY: new_data points to capacity valid zeroed words.
+            let dst = unsafe { std::slice::from_raw_parts_mut(new_data, words) };
+            dst.copy_from_slice(self.as_slice());
+        }
+        // Drops our reference to the shared region; since the count was > 1
+        // this only decrements, it doesn't free.
+        arena_free(self.data, self.capacity as usize, words);
+        self.data = new_data;
+        self.capacity = new_capacity as u32;
+        self.generation = arena_generation();
+    }
+
     pub fn set(&mut self, index: usize, val: bool) {
+        self.make_unique();
         let word_idx = index / 64;
         let bit_idx = index % 64;
         let words = self.as_full_slice_mut();
@@ -169,7 +355,7 @@ impl BitVec {
     fn ensure_words(&mut self, n_words: usize) {
         if n_words > self.capacity as usize {
             let new_cap = n_words.max((self.capacity as usize) * 2);
-            let new_data = arena_alloc(new_cap);
+            let (new_data, new_cap) = arena_alloc(new_cap);
             let old = self.words_in_use();
             if old > 0 {
                 // SAFETY: new_data points to new_cap valid zeroed words; old <= capacity.
@@ -180,11 +366,13 @@ impl BitVec {
             let old_data = self.data;
             self.data = new_data;
             self.capacity = new_cap as u32;
+            self.generation = arena_generation();
             arena_free(old_data, old_cap as usize, old);
         }
     }
 
     pub fn resize(&mut self, new_len: usize, val: bool) {
+        self.make_unique();
         let new_words = new_len.div_ceil(64);
         let old_words = self.words_in_use();
         self.ensure_words(new_words);
@@ -217,6 +405,7 @@ impl BitVec {
         if self.num_bits == 0 {
             return None;
         }
+        self.make_unique();
         self.num_bits -= 1;
         let word_idx = self.num_bits as usize / 64;
         let bit_idx = self.num_bits as usize % 64;
@@ -239,20 +428,22 @@ impl BitVec {
         if other_words == 0 {
             return false;
         }
+        self.make_unique();
         let self_words = self.words_in_use();
         if other_words > self.capacity as usize {
             // Need a larger arena region.
-            let new_data = arena_alloc(other_words);
+            let (new_data, new_cap) = arena_alloc(other_words);
             if self_words > 0 {
-                // SAFETY: new_data points to other_words valid zeroed words; self_words <= capacity.
+                // SAFETY: new_data points to new_cap valid zeroed words; self_words <= capacity.
                 let dst = unsafe { std::slice::from_raw_parts_mut(new_data, self_words) };
                 dst.copy_from_slice(self.as_slice());
             }
-            // Arena memory is pre-zeroed, so words self_words..other_words are already 0.
+            // Arena memory is pre-zeroed, so words self_words..new_cap are already 0.
             let old_cap = self.capacity;
             let old_data = self.data;
             self.data = new_data;
-            self.capacity = other_words as u32;
+            self.capacity = new_cap as u32;
+            self.generation = arena_generation();
             arena_free(old_data, old_cap as usize, self_words);
         } else if other_words > self_words {
             // Have capacity, but clear any stale data in the region we're about to OR into.
@@ -271,11 +462,185 @@ impl BitVec {
         }
         any_new != 0
     }
+
+    /// Word-level AND: self &= other. Returns true if any bits were cleared.
+    pub fn intersect_with(&mut self, other: &Self) -> bool {
+        self.make_unique();
+        let self_words = self.words_in_use();
+        let other_words = other.words_in_use();
+        let min_words = self_words.min(other_words);
+        let other_slice = other.as_slice();
+        let self_slice = self.as_full_slice_mut();
+        let mut cleared = 0u64;
+        for i in 0..min_words {
+            let new = self_slice[i] & other_slice[i];
+            cleared |= self_slice[i] ^ new;
+            self_slice[i] = new;
+        }
+        // Beyond other's length, other is implicitly all-zero, so those
+        // words of self are cleared too.
+        for w in &mut self_slice[min_words..self_words] {
+            cleared |= *w;
+            *w = 0;
+        }
+        cleared != 0
+    }
+
+    /// Word-level ANDNOT: self &= !other. Returns true if any bits were cleared.
+    pub fn difference_with(&mut self, other: &Self) -> bool {
+        self.make_unique();
+        let self_words = self.words_in_use();
+        let other_words = other.words_in_use().min(self_words);
+        let other_slice = other.as_slice();
+        let self_slice = self.as_full_slice_mut();
+        let mut cleared = 0u64;
+        for i in 0..other_words {
+            let removed = self_slice[i] & other_slice[i];
+            self_slice[i] &= !other_slice[i];
+            cleared |= removed;
+        }
+        // Beyond other's length, other is implicitly all-zero, so the rest
+        // of self is left untouched (self & !0 == self).
+        cleared != 0
+    }
+
+    /// Word-level XOR: self ^= other. Returns true if self changed.
+    pub fn symmetric_difference_with(&mut self, other: &Self) -> bool {
+        let other_words = other.words_in_use();
+        if other_words == 0 {
+            return false;
+        }
+        self.make_unique();
+        let self_words = self.words_in_use();
+        if other_words > self.capacity as usize {
+            // Need a larger arena region.
+            let (new_data, new_cap) = arena_alloc(other_words);
+            if self_words > 0 {
+                // SAFETY: new_data points to new_cap valid zeroed words; self_words <= capacity.
+                let dst = unsafe { std::slice::from_raw_parts_mut(new_data, self_words) };
+                dst.copy_from_slice(self.as_slice());
+            }
+            // Arena memory is pre-zeroed, so words self_words..new_cap are already 0.
+            let old_cap = self.capacity;
+            let old_data = self.data;
+            self.data = new_data;
+            self.capacity = new_cap as u32;
+            self.generation = arena_generation();
+            arena_free(old_data, old_cap as usize, self_words);
+        } else if other_words > self_words {
+            // Have capacity, but clear any stale data in the region we're about to XOR into.
+            self.as_full_slice_mut()[self_words..other_words].fill(0);
+        }
+        if other.num_bits > self.num_bits {
+            self.num_bits = other.num_bits;
+        }
+        let other_slice = other.as_slice();
+        let self_slice = &mut self.as_full_slice_mut()[..other_words];
+        let mut changed = 0u64;
+        for (sw, &ow) in self_slice.iter_mut().zip(other_slice) {
+            changed |= ow;
+            *sw ^= ow;
+        }
+        changed != 0
+    }
+
+    /// Returns `true` if every bit set in `self` is also set in `other`
+    /// (i.e. `self` is a subset of `other`), matching the convention of
+    /// `HashSet::is_subset`/`BTreeSet::is_subset`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        let sw_slice = self.as_slice();
+        let ow_slice = other.as_slice();
+        let min_len = sw_slice.len().min(ow_slice.len());
+        for (&sw, &ow) in sw_slice.iter().zip(ow_slice).take(min_len) {
+            if sw & !ow != 0 {
+                return false;
+            }
+        }
+        // Beyond other's length, other is implicitly all-zero, so any bit
+        // set in self's remaining words isn't contained in other.
+        sw_slice[min_len..].iter().all(|&sw| sw == 0)
+    }
+
+    /// Returns `true` if `self` and `other` share no set bits.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        let sw_slice = self.as_slice();
+        let ow_slice = other.as_slice();
+        !sw_slice.iter().zip(ow_slice).any(|(&sw, &ow)| sw & ow != 0)
+    }
+
+    /// Total number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.as_slice().iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Stream this `BitVec` out as a little-endian bit count followed by its
+    /// in-use words, taken directly from `as_slice()` so no per-bit work is
+    /// done. Lets a whole table of `BitVec`s be written to a cache file.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.num_bits.to_le_bytes())?;
+        for word in self.as_slice() {
+            w.write_all(&word.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Read back a `BitVec` written by `write_to`.
+    pub fn read_from<R: Read>(r: &mut R) -> io::Result<Self> {
+        let mut len_buf = [0u8; 4];
+        r.read_exact(&mut len_buf)?;
+        let num_bits = u32::from_le_bytes(len_buf);
+        let n_words = (num_bits as usize).div_ceil(64);
+        if n_words == 0 {
+            return Ok(Self::new());
+        }
+        if n_words > MAX_ARENA_ALLOC_WORDS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "BitVec: encoded bit count exceeds the maximum arena allocation size",
+            ));
+        }
+        let mut v = Self::with_capacity(num_bits as usize);
+        {
+            let words = &mut v.as_full_slice_mut()[..n_words];
+            let mut word_buf = [0u8; 8];
+            for word in words {
+                r.read_exact(&mut word_buf)?;
+                *word = u64::from_le_bytes(word_buf);
+            }
+        }
+        v.num_bits = num_bits;
+        // Re-establish the invariant that bits beyond num_bits are zero, in
+        // case the stream didn't already uphold it.
+        if !(num_bits as usize).is_multiple_of(64) {
+            let mask = (1u64 << (num_bits % 64)) - 1;
+            v.as_full_slice_mut()[n_words - 1] &= mask;
+        }
+        Ok(v)
+    }
+
+    /// Encode this `BitVec` to a byte buffer (see `write_to`).
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(4 + self.words_in_use() * 8);
+        self.write_to(&mut buf)
+            .expect("writing to a Vec<u8> never fails");
+        buf
+    }
+
+    /// Decode a `BitVec` previously produced by `encode`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is truncated, or if the encoded bit count is too
+    /// large to allocate (e.g. a corrupt or adversarial buffer).
+    pub fn decode(bytes: &[u8]) -> Self {
+        Self::read_from(&mut io::Cursor::new(bytes)).expect("BitVec::decode: invalid buffer")
+    }
 }
 
 impl Drop for BitVec {
     fn drop(&mut self) {
         if self.capacity > 0 {
+            self.debug_check_generation();
             arena_free(self.data, self.capacity as usize, self.words_in_use());
         }
     }
@@ -288,19 +653,22 @@ impl Default for BitVec {
 }
 
 impl Clone for BitVec {
+    /// Cheap handle copy: bumps the shared region's refcount rather than
+    /// copying any words. The clone becomes a private copy on its first
+    /// write, via `make_unique()`.
     fn clone(&self) -> Self {
-        let words = self.words_in_use();
-        if words == 0 {
+        self.debug_check_generation();
+        if self.capacity == 0 {
             return Self::new();
         }
-        let new_data = arena_alloc(words);
-        // SAFETY: new_data points to `words` valid zeroed words.
-        let dst = unsafe { std::slice::from_raw_parts_mut(new_data, words) };
-        dst.copy_from_slice(self.as_slice());
+        // SAFETY: data was returned by arena_alloc, so data[-1] is the header.
+        let header = unsafe { self.data.sub(1) };
+        unsafe { *header += 1 };
         Self {
-            data: new_data,
+            data: self.data,
             num_bits: self.num_bits,
-            capacity: words as u32,
+            capacity: self.capacity,
+            generation: self.generation,
         }
     }
 }
@@ -404,3 +772,131 @@ impl Iterator for SetBitsIter<'_> {
         Some(self.word_idx * 64 + bit)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `with_capacity` only reserves arena words; build a `BitVec` whose
+    /// `num_bits` actually covers `len` so `get`/`set` can address it.
+    fn zeroed(len: usize) -> BitVec {
+        let mut v = BitVec::with_capacity(len);
+        v.resize(len, false);
+        v
+    }
+
+    #[test]
+    fn clone_is_copy_on_write() {
+        let _scope = ArenaScope::new();
+        let mut a = zeroed(128);
+        a.set(0, true);
+        let b = a.clone();
+        a.set(1, true);
+        assert!(a.get(1).unwrap());
+        assert!(!b.get(1).unwrap());
+        assert!(b.get(0).unwrap());
+    }
+
+    #[test]
+    fn reset_reuses_retained_chunk_without_growing() {
+        {
+            let _scope = ArenaScope::new();
+            // Allocate enough words to force at least a second chunk.
+            let _first = zeroed(64);
+            let _big = zeroed(ARENA_CHUNK_WORDS * 64);
+        }
+        let chunks_after_first_reset = WORD_ARENA.with(|a| a.borrow().chunks.len());
+        {
+            let _scope = ArenaScope::new();
+            let _small = zeroed(64);
+        }
+        let chunks_after_second_reset = WORD_ARENA.with(|a| a.borrow().chunks.len());
+        assert_eq!(chunks_after_first_reset, 1);
+        assert_eq!(chunks_after_second_reset, 1);
+    }
+
+    #[test]
+    fn reset_before_any_allocation_does_not_panic() {
+        // Run on a fresh thread so its WordArena starts out genuinely empty
+        // (chunks.len() == 0), matching ArenaScope::new() dropping before
+        // anything has ever been allocated on the thread.
+        std::thread::spawn(|| {
+            drop(ArenaScope::new());
+            let _v = zeroed(64);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn is_subset_matches_self_subset_of_other() {
+        let _scope = ArenaScope::new();
+        let mut a = zeroed(8);
+        a.set(0, true);
+        let mut b = zeroed(8);
+        b.set(0, true);
+        b.set(1, true);
+        assert!(a.is_subset(&b));
+        assert!(!b.is_subset(&a));
+    }
+
+    #[test]
+    fn set_predicates_and_ops() {
+        let _scope = ArenaScope::new();
+        let mut a = zeroed(8);
+        a.set(0, true);
+        a.set(1, true);
+        let mut b = zeroed(8);
+        b.set(1, true);
+        b.set(2, true);
+
+        assert!(!a.is_disjoint(&b));
+
+        let mut intersect = a.clone();
+        intersect.intersect_with(&b);
+        assert!(intersect.get(1).unwrap());
+        assert!(!intersect.get(0).unwrap());
+        assert!(!intersect.get(2).unwrap());
+
+        let mut difference = a.clone();
+        difference.difference_with(&b);
+        assert!(difference.get(0).unwrap());
+        assert!(!difference.get(1).unwrap());
+
+        let mut symmetric = a.clone();
+        symmetric.symmetric_difference_with(&b);
+        assert!(symmetric.get(0).unwrap());
+        assert!(!symmetric.get(1).unwrap());
+        assert!(symmetric.get(2).unwrap());
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let _scope = ArenaScope::new();
+        let mut a = zeroed(200);
+        a.set(0, true);
+        a.set(64, true);
+        a.set(199, true);
+        let decoded = BitVec::decode(&a.encode());
+        assert!(a == decoded);
+    }
+
+    #[test]
+    fn decode_rejects_oversized_bit_count_instead_of_panicking() {
+        let _scope = ArenaScope::new();
+        match BitVec::read_from(&mut io::Cursor::new(u32::MAX.to_le_bytes())) {
+            Err(err) => assert_eq!(err.kind(), io::ErrorKind::InvalidData),
+            Ok(_) => panic!("an encoded bit count this large must be rejected, not allocated"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the maximum arena allocation size")]
+    fn alloc_panics_clearly_instead_of_indexing_free_list_out_of_bounds() {
+        // Any growth path that requests a too-large size (not just
+        // read_from's untrusted-input path) must fail with a clear message
+        // rather than indexing `free_list` out of bounds.
+        let mut arena = WordArena::new();
+        arena.alloc(1 << 30);
+    }
+}